@@ -0,0 +1,519 @@
+// Whole-subtree snapshot/restore on top of the single-entry metadata codec.
+//
+// Walks a directory with the `lstat`/entry-type logic from `meta` and
+// emits a single sequential stream: a header, then for each node a framed
+// `ArchivedEntryMetaData` record (reusing `CodecSerializer` via
+// `EntryMetaData::to_bytes`) followed, for regular files, by chunked
+// content, and a directory-boundary marker so the hierarchy can be
+// rebuilt without random access. This is the same sequential
+// create+extract model archive formats like pxar use. The walk that
+// produces the stream runs on its own thread and feeds the returned
+// reader through a bounded channel, so producing an archive never
+// requires holding a whole subtree's metadata or file content in memory.
+
+use std::{
+    collections::HashMap,
+    ffi::{CString, OsStr, OsString},
+    fs::File,
+    io::{self, Read, Write},
+    os::unix::ffi::OsStrExt,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender},
+};
+
+use rkyv::Deserialize;
+
+use crate::{
+    meta::{frame_record, EntryHash, EntryMetaData, EntryType, HardLinkTracker},
+    wrappers, BackStore,
+};
+
+const MAGIC: &[u8; 8] = b"RKYVARCH";
+const FORMAT_VERSION: u32 = 1;
+
+const RECORD_ENTRY: u8 = 1;
+const RECORD_DIR_END: u8 = 2;
+const RECORD_EOF: u8 = 3;
+
+/// Regular file content is streamed in fixed-size chunks rather than one
+/// big write, so archiving doesn't have to hold a whole file in memory.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Whole-subtree snapshot/restore built on [`EntryMetaData`].
+pub struct Archive;
+
+/// Pull end of a [`ChannelWriter`], returned by [`Archive::create`].
+///
+/// The walk that produces the archive bytes runs on its own thread and
+/// pushes each write through a bounded channel, so `read` only ever holds
+/// one in-flight chunk (at most `CHANNEL_CAPACITY` buffered ahead of it)
+/// instead of the whole subtree's metadata and file content. A full
+/// channel blocks the writer thread, which throttles the walk to the pace
+/// the consumer actually reads at.
+struct ArchiveReader {
+    rx: Receiver<io::Result<Vec<u8>>>,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl Read for ArchiveReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.buf.len() {
+                let n = out.len().min(self.buf.len() - self.pos);
+                out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            match self.rx.recv() {
+                Ok(Ok(chunk)) => {
+                    self.buf = chunk;
+                    self.pos = 0;
+                }
+                Ok(Err(e)) => {
+                    self.done = true;
+                    return Err(e);
+                }
+                Err(_) => {
+                    self.done = true;
+                    return Ok(0);
+                }
+            }
+        }
+    }
+}
+
+/// Push end of an [`ArchiveReader`]: a [`Write`] that forwards every write
+/// as its own channel message instead of buffering them.
+struct ChannelWriter {
+    tx: SyncSender<io::Result<Vec<u8>>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "archive reader dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// How many chunks the writer thread may get ahead of the reader before
+/// blocking, bounding how much of the archive can be buffered in memory
+/// at once regardless of subtree size.
+const CHANNEL_CAPACITY: usize = 4;
+
+impl Archive {
+    /// Walk `root` (resolved through `fs`, same as [`EntryMetaData::new`])
+    /// and return a stream snapshotting the whole subtree: a header, then
+    /// one framed entry record per node, with chunked content for regular
+    /// files, bounded by directory-boundary markers.
+    ///
+    /// The walk runs on a background thread and streams bytes to the
+    /// returned reader through a bounded channel as they're produced, so
+    /// `create` never holds a whole subtree's metadata or file content in
+    /// memory at once.
+    pub fn create<B, Partial>(fs: &B, root: Partial) -> io::Result<impl Read>
+    where
+        B: BackStore + std::fmt::Debug + Clone + Send + 'static,
+        Partial: AsRef<Path>,
+    {
+        let root = root.as_ref().to_path_buf();
+        let fs = fs.clone();
+        let (tx, rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+
+        std::thread::spawn(move || {
+            let mut writer = ChannelWriter { tx };
+            if let Err(e) = Self::write_stream(&fs, &root, &mut writer) {
+                let _ = writer.tx.send(Err(e));
+            }
+        });
+
+        Ok(ArchiveReader {
+            rx,
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    fn write_stream<B>(fs: &B, root: &Path, out: &mut impl Write) -> io::Result<()>
+    where
+        B: BackStore + std::fmt::Debug + Clone,
+    {
+        out.write_all(MAGIC)?;
+        out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+
+        let mut links = HardLinkTracker::new();
+        let parent = root.parent().unwrap_or_else(|| Path::new(""));
+        let name = root.file_name().unwrap_or_else(|| OsStr::new(""));
+
+        let root_emd =
+            EntryMetaData::<B>::new(fs, parent, name, &mut links).map_err(errno_to_io)?;
+        let is_dir = matches!(root_emd.entry_type, EntryType::Dir);
+        write_entry_record(&root_emd, out)?;
+
+        if is_dir {
+            Self::walk_dir(fs, root, &mut links, out)?;
+        }
+
+        out.write_all(&[RECORD_EOF])?;
+        Ok(())
+    }
+
+    fn walk_dir<B>(
+        fs: &B,
+        partial_dir: &Path,
+        links: &mut HardLinkTracker,
+        out: &mut impl Write,
+    ) -> io::Result<()>
+    where
+        B: BackStore + std::fmt::Debug + Clone,
+    {
+        let real_dir = fs.highest_path(partial_dir).map_err(errno_to_io)?;
+        let mut names: Vec<OsString> = std::fs::read_dir(&real_dir)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<Result<_, _>>()?;
+        names.sort();
+
+        for name in names {
+            let emd =
+                EntryMetaData::<B>::new(fs, partial_dir, &name, links).map_err(errno_to_io)?;
+            let child_partial = partial_dir.join(&name);
+            let entry_type = emd.entry_type.clone();
+            write_entry_record(&emd, out)?;
+
+            match entry_type {
+                EntryType::File => {
+                    let real_child = fs.highest_path(&child_partial).map_err(errno_to_io)?;
+                    write_chunks(&real_child, out)?;
+                }
+                EntryType::Dir => {
+                    Self::walk_dir(fs, &child_partial, links, out)?;
+                }
+                _ => {}
+            }
+        }
+
+        out.write_all(&[RECORD_DIR_END])?;
+        Ok(())
+    }
+
+    /// Restore a stream produced by [`Archive::create`] into `dest`,
+    /// recreating files, permissions, owners/groups (best-effort),
+    /// xattrs/ACLs, symlinks and hardlinks. Each entry's content hash is
+    /// re-verified against the one recorded at archive time.
+    pub fn extract<B, R>(mut reader: R, dest: &Path) -> io::Result<()>
+    where
+        B: BackStore + std::fmt::Debug + Clone,
+        R: Read,
+    {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an rkyv-codec archive",
+            ));
+        }
+        let mut version_bytes = [0u8; 4];
+        reader.read_exact(&mut version_bytes)?;
+        if u32::from_le_bytes(version_bytes) != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unsupported archive format version",
+            ));
+        }
+
+        let mut extracted = HashMap::new();
+
+        let root_emd = read_entry_record::<B, _>(&mut reader)?;
+        let root_is_dir = matches!(root_emd.entry_type, EntryType::Dir);
+        restore_entry(&root_emd, dest, &mut extracted)?;
+
+        let mut stack = Vec::new();
+        if root_is_dir {
+            stack.push(dest.to_path_buf());
+        }
+
+        loop {
+            let mut tag = [0u8; 1];
+            reader.read_exact(&mut tag)?;
+            match tag[0] {
+                RECORD_EOF => break,
+                RECORD_DIR_END => {
+                    stack.pop();
+                }
+                RECORD_ENTRY => {
+                    let emd = read_entry_record::<B, _>(&mut reader)?;
+                    let dir = stack.last().cloned().unwrap_or_else(|| dest.to_path_buf());
+                    let target = dir.join(&emd.name);
+
+                    restore_entry(&emd, &target, &mut extracted)?;
+                    if matches!(emd.entry_type, EntryType::File) {
+                        restore_content(&mut reader, &target)?;
+                    }
+                    if matches!(emd.entry_type, EntryType::Dir) {
+                        stack.push(target);
+                    }
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unknown archive record tag",
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn errno_to_io(errno: i32) -> io::Error {
+    io::Error::from_raw_os_error(errno)
+}
+
+fn write_entry_record<B>(emd: &EntryMetaData<B>, out: &mut impl Write) -> io::Result<()>
+where
+    B: BackStore + std::fmt::Debug + Clone,
+{
+    let hash = emd.content_hash();
+    let payload = emd.to_bytes().map_err(errno_to_io)?;
+    let frame = frame_record(&hash, &payload);
+
+    out.write_all(&[RECORD_ENTRY])?;
+    out.write_all(&frame)?;
+    Ok(())
+}
+
+fn read_entry_record<B, R>(reader: &mut R) -> io::Result<EntryMetaData<B>>
+where
+    B: BackStore + std::fmt::Debug + Clone,
+    R: Read,
+{
+    let mut len_bytes = [0u8; 4];
+    reader.read_exact(&mut len_bytes)?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+
+    let mut algo_tag = [0u8; 1];
+    reader.read_exact(&mut algo_tag)?;
+
+    let mut digest = [0u8; 32];
+    reader.read_exact(&mut digest)?;
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+
+    let expected = EntryHash::from_tag(algo_tag[0], digest).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "unknown content hash algorithm")
+    })?;
+
+    let archived = rkyv::check_archived_root::<EntryMetaData<B>>(&payload).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "corrupt or tampered entry record",
+        )
+    })?;
+    let emd: EntryMetaData<B> =
+        crate::meta::ArchivedEntryMetaData::deserialize(archived, &mut rkyv::Infallible).unwrap();
+
+    let actual = match expected {
+        EntryHash::SHA2(_) => emd.content_hash(),
+        EntryHash::SHA3(_) => emd.content_hash_sha3(),
+    };
+    if actual.as_bytes() != expected.as_bytes() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "entry failed content hash verification",
+        ));
+    }
+
+    Ok(emd)
+}
+
+fn write_chunks(path: &Path, out: &mut impl Write) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        out.write_all(&(n as u32).to_le_bytes())?;
+        out.write_all(&buf[..n])?;
+    }
+    out.write_all(&0u32.to_le_bytes())?;
+    Ok(())
+}
+
+fn restore_content<R: Read>(reader: &mut R, path: &Path) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    loop {
+        let mut len_bytes = [0u8; 4];
+        reader.read_exact(&mut len_bytes)?;
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        if len == 0 {
+            break;
+        }
+        let mut chunk = vec![0u8; len];
+        reader.read_exact(&mut chunk)?;
+        file.write_all(&chunk)?;
+    }
+    Ok(())
+}
+
+fn restore_entry<B>(
+    emd: &EntryMetaData<B>,
+    target: &Path,
+    extracted: &mut HashMap<PathBuf, PathBuf>,
+) -> io::Result<()>
+where
+    B: BackStore + std::fmt::Debug + Clone,
+{
+    if target.symlink_metadata().is_ok() && !matches!(emd.entry_type, EntryType::Dir) {
+        std::fs::remove_file(target)?;
+    }
+
+    match &emd.entry_type {
+        EntryType::Dir => {
+            std::fs::create_dir_all(target)?;
+        }
+        EntryType::Symlink {
+            target: link_target,
+        } => {
+            std::os::unix::fs::symlink(link_target, target)?;
+        }
+        EntryType::HardLink { to } => {
+            let source = extracted.get(to).cloned().unwrap_or_else(|| to.clone());
+            std::fs::hard_link(&source, target)?;
+            extracted.insert(emd.parent.join(&emd.name), target.to_path_buf());
+            return Ok(());
+        }
+        EntryType::Fifo => {
+            mkfifo(target, emd.stats.st_mode)?;
+        }
+        EntryType::Socket | EntryType::CharDev | EntryType::BlockDev => {
+            mknod(target, emd.stats.st_mode, emd.stats.st_rdev)?;
+        }
+        EntryType::File => {
+            File::create(target)?;
+        }
+    }
+
+    extracted.insert(emd.parent.join(&emd.name), target.to_path_buf());
+
+    // Best-effort from here down: a restore running as a non-privileged
+    // user can't chown, and not every target filesystem supports
+    // xattrs/ACLs, so failures are swallowed rather than aborting the
+    // whole extract.
+    if !matches!(emd.entry_type, EntryType::Symlink { .. }) {
+        let _ = std::fs::set_permissions(
+            target,
+            std::fs::Permissions::from_mode(emd.stats.st_mode & 0o7777),
+        );
+    }
+    let _ = lchown(target, emd.stats.st_uid, emd.stats.st_gid);
+
+    if let Some(xattrs) = &emd.xattrs {
+        for (name, value) in xattrs {
+            let name = OsStr::from_bytes(name.as_bytes());
+            let _ = wrappers::setxattr(target, name, value);
+        }
+    }
+    if let Some(acls) = &emd.acls {
+        if let Some(access) = &acls.access {
+            let _ = wrappers::setacl(target, wrappers::AclType::Access, access);
+        }
+        if let Some(default) = &acls.default {
+            let _ = wrappers::setacl(target, wrappers::AclType::Default, default);
+        }
+    }
+
+    Ok(())
+}
+
+fn path_to_cstring(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains NUL byte"))
+}
+
+fn mkfifo(path: &Path, mode: libc::mode_t) -> io::Result<()> {
+    let path_c = path_to_cstring(path)?;
+    let ret = unsafe { libc::mkfifo(path_c.as_ptr(), mode & 0o7777) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn mknod(path: &Path, mode: libc::mode_t, dev: libc::dev_t) -> io::Result<()> {
+    let path_c = path_to_cstring(path)?;
+    let ret = unsafe { libc::mknod(path_c.as_ptr(), mode, dev) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn lchown(path: &Path, uid: libc::uid_t, gid: libc::gid_t) -> io::Result<()> {
+    let path_c = path_to_cstring(path)?;
+    let ret = unsafe { libc::lchown(path_c.as_ptr(), uid, gid) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::MetadataExt;
+
+    use super::*;
+    use crate::AiFs;
+
+    /// `b.txt` is a hardlink to `a.txt` and `c.txt` is a relative symlink
+    /// to `a.txt`. Round-tripping through `create`/`extract` must
+    /// reconstruct both relationships against the *extracted* tree, not
+    /// resolve `HardLink { to }` against the original backstore path.
+    #[test]
+    fn test_hardlink_and_symlink_round_trip() {
+        let src = Path::new("/tmp/foo/highest/archive_test_src");
+        let _ = std::fs::remove_dir_all(src);
+        std::fs::create_dir_all(src).unwrap();
+        std::fs::write(src.join("a.txt"), b"hello world").unwrap();
+        std::fs::hard_link(src.join("a.txt"), src.join("b.txt")).unwrap();
+        std::os::unix::fs::symlink("a.txt", src.join("c.txt")).unwrap();
+
+        let fs = AiFs::new("/tmp/aifs", "/tmp/lower", None);
+        let mut stream = Archive::create(&fs, "archive_test_src").unwrap();
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes).unwrap();
+
+        let dest = Path::new("/tmp/foo/archive_test_dest");
+        let _ = std::fs::remove_dir_all(dest);
+        Archive::extract::<AiFs, _>(io::Cursor::new(bytes), dest).unwrap();
+
+        assert_eq!(std::fs::read(dest.join("a.txt")).unwrap(), b"hello world");
+        assert_eq!(std::fs::read(dest.join("b.txt")).unwrap(), b"hello world");
+
+        let a_ino = std::fs::metadata(dest.join("a.txt")).unwrap().ino();
+        let b_ino = std::fs::metadata(dest.join("b.txt")).unwrap().ino();
+        assert_eq!(
+            a_ino, b_ino,
+            "b.txt should be hard-linked to the extracted a.txt"
+        );
+
+        let link_target = std::fs::read_link(dest.join("c.txt")).unwrap();
+        assert_eq!(link_target, Path::new("a.txt"));
+    }
+}
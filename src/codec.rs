@@ -99,7 +99,8 @@ pub mod ser {
 use rkyv::with::{ArchiveWith, DeserializeWith};
 use rkyv_with::{ArchiveWith, DeserializeWith};
 
-#[derive(Debug, Clone, rkyv::Archive, ArchiveWith)]
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Deserialize, ArchiveWith, DeserializeWith)]
+#[archive(check_bytes)]
 #[archive_with(from(::nix::sys::stat::FileStat))]
 #[archive_attr(derive(Debug))]
 pub struct FileStatCodec {
@@ -146,7 +147,8 @@ impl From<FileStatCodec> for ::libc::stat {
 }
 
 /// Serialize and Deserialize User
-#[derive(Debug, Clone, rkyv::Archive, ArchiveWith)]
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Deserialize, ArchiveWith, DeserializeWith)]
+#[archive(check_bytes)]
 #[archive_with(from(::nix::unistd::User))]
 #[archive_attr(derive(Debug))]
 pub struct UserCodec {
@@ -172,7 +174,8 @@ pub struct UserCodec {
 }
 
 /// Serialize and Deserialize Group
-#[derive(Debug, Clone, rkyv::Archive, ArchiveWith)]
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Deserialize, ArchiveWith, DeserializeWith)]
+#[archive(check_bytes)]
 #[archive_with(from(::nix::unistd::Group))]
 #[archive_attr(derive(Debug))]
 pub struct GroupCodec {
@@ -187,16 +190,56 @@ pub struct GroupCodec {
     pub mem: Vec<String>,
 }
 
-#[derive(Debug, Clone, rkyv::Archive, ArchiveWith, DeserializeWith)]
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Deserialize, ArchiveWith, DeserializeWith)]
+#[archive(check_bytes)]
 #[archive_with(from(::nix::unistd::Gid))]
 #[archive_attr(derive(Debug))]
 pub struct GidCodec(::libc::gid_t);
 
-#[derive(Debug, Clone, rkyv::Archive, ArchiveWith, DeserializeWith)]
+#[derive(Debug, Clone, rkyv::Archive, rkyv::Deserialize, ArchiveWith, DeserializeWith)]
+#[archive(check_bytes)]
 #[archive_with(from(::nix::unistd::Uid))]
 #[archive_attr(derive(Debug))]
 pub struct UidCodec(::libc::uid_t);
 
+impl From<UidCodec> for ::nix::unistd::Uid {
+    fn from(codec: UidCodec) -> Self {
+        ::nix::unistd::Uid::from_raw(codec.0)
+    }
+}
+
+impl From<GidCodec> for ::nix::unistd::Gid {
+    fn from(codec: GidCodec) -> Self {
+        ::nix::unistd::Gid::from_raw(codec.0)
+    }
+}
+
+impl From<UserCodec> for ::nix::unistd::User {
+    fn from(codec: UserCodec) -> Self {
+        Self {
+            name: codec.name,
+            passwd: codec.passwd,
+            uid: codec.uid.into(),
+            gid: codec.gid.into(),
+            #[cfg(not(all(target_os = "android", target_pointer_width = "32")))]
+            gecos: codec.gecos,
+            dir: std::path::PathBuf::from(codec.dir),
+            shell: std::path::PathBuf::from(codec.shell),
+        }
+    }
+}
+
+impl From<GroupCodec> for ::nix::unistd::Group {
+    fn from(codec: GroupCodec) -> Self {
+        Self {
+            name: codec.name,
+            passwd: codec.passwd,
+            gid: codec.gid.into(),
+            mem: codec.mem,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,6 @@
 use std::{path::{PathBuf, Path}, fmt::Debug};
 
+mod archive;
 mod codec;
 mod meta;
 mod wrappers;
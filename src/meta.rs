@@ -1,7 +1,8 @@
 use std::{
     ffi::{CString, OsStr, OsString},
+    io::{Read, Write},
     marker::PhantomData,
-    os::unix::prelude::OsStringExt,
+    os::unix::prelude::{OsStrExt, OsStringExt},
     path::{Path, PathBuf},
     ptr,
     time::SystemTime, fmt::Formatter,
@@ -18,20 +19,210 @@ use rkyv::{
     with::AsString,
     AlignedVec, Archive, Deserialize, Serialize,
 };
+use sha2::{Digest as _, Sha256};
+use sha3::{Digest as _, Sha3_256};
 
 /// Metadata Entry Hash
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Archive, Serialize, Deserialize)]
+#[archive(check_bytes)]
 pub enum EntryHash {
     SHA2([u8; 32]),
     SHA3([u8; 32]),
 }
 
+impl EntryHash {
+    /// The raw digest bytes, regardless of which algorithm produced them.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        match self {
+            EntryHash::SHA2(bytes) => bytes,
+            EntryHash::SHA3(bytes) => bytes,
+        }
+    }
+
+    /// Single-byte algorithm tag used to frame this hash in a journal or archive.
+    pub(crate) fn algo_tag(&self) -> u8 {
+        match self {
+            EntryHash::SHA2(_) => 0,
+            EntryHash::SHA3(_) => 1,
+        }
+    }
+
+    /// Reconstruct an `EntryHash` from a journal/archive's algorithm tag and digest.
+    pub(crate) fn from_tag(tag: u8, digest: [u8; 32]) -> Option<Self> {
+        match tag {
+            0 => Some(EntryHash::SHA2(digest)),
+            1 => Some(EntryHash::SHA3(digest)),
+            _ => None,
+        }
+    }
+
+    /// Fold a previous chain head hash into a new content hash to get the
+    /// next chain head: `H(prev_head_hash || content_hash)`, using the
+    /// same algorithm as `content`. `prev` is `None` for the first entry
+    /// in a journal, which chains against the empty string.
+    ///
+    /// This is what makes `MetaDataHistory` a Merkle chain: each stored
+    /// hash commits to everything written before it, so tampering with or
+    /// dropping an earlier entry changes every hash after it.
+    fn chain(prev: Option<&EntryHash>, content: &EntryHash) -> EntryHash {
+        match content {
+            EntryHash::SHA2(content_digest) => {
+                let mut hasher = Sha256::new();
+                if let Some(prev) = prev {
+                    hasher.update(prev.as_bytes());
+                }
+                hasher.update(content_digest);
+                let mut digest = [0u8; 32];
+                digest.copy_from_slice(hasher.finalize().as_slice());
+                EntryHash::SHA2(digest)
+            }
+            EntryHash::SHA3(content_digest) => {
+                let mut hasher = Sha3_256::new();
+                if let Some(prev) = prev {
+                    hasher.update(prev.as_bytes());
+                }
+                hasher.update(content_digest);
+                let mut digest = [0u8; 32];
+                digest.copy_from_slice(hasher.finalize().as_slice());
+                EntryHash::SHA3(digest)
+            }
+        }
+    }
+}
+
+/// Which digest algorithm to use for a new [`EntryMetaData::write`] entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgo {
+    Sha2,
+    Sha3,
+}
+
+/// Walk the framed records of a `.metadata.history` journal, yielding each
+/// record's stored `(hash, payload)` pair in append order.
+///
+/// Shared by [`EntryMetaData::write`] (to find the current chain head
+/// before appending) and [`MetaDataHistory::load`] (to replay and verify
+/// the whole chain).
+fn parse_frames(bytes: &[u8]) -> impl Iterator<Item = (EntryHash, &[u8])> {
+    let mut cursor = 0usize;
+    std::iter::from_fn(move || loop {
+        if cursor + 4 > bytes.len() {
+            return None;
+        }
+        let len = u32::from_le_bytes(bytes[cursor..cursor + 4].try_into().unwrap()) as usize;
+        let record_start = cursor;
+        cursor += 4;
+        if cursor + 1 + 32 + len > bytes.len() {
+            cursor = record_start;
+            return None;
+        }
+        let algo_tag = bytes[cursor];
+        cursor += 1;
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&bytes[cursor..cursor + 32]);
+        cursor += 32;
+        let payload = &bytes[cursor..cursor + len];
+        cursor += len;
+
+        match EntryHash::from_tag(algo_tag, digest) {
+            Some(hash) => return Some((hash, payload)),
+            None => continue,
+        }
+    })
+}
+
+/// Append a length-prefixed field to a canonical encoding buffer.
+///
+/// The `u32` length prefix keeps variable-length fields (names, paths,
+/// xattr values) from running together when concatenated, so the hash
+/// is sensitive to field boundaries and not just total byte content.
+fn write_framed(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Frame a serialized entry payload as `[u32 len][u8 hash-algo][32-byte
+/// hash][len bytes of payload]`. Shared by the `.metadata.history` journal
+/// and the `archive` module's per-node records.
+pub(crate) fn frame_record(hash: &EntryHash, payload: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(4 + 1 + 32 + payload.len());
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.push(hash.algo_tag());
+    record.extend_from_slice(hash.as_bytes());
+    record.extend_from_slice(payload);
+    record
+}
+
 /// Extended attributes
 
+/// The kind of filesystem node an entry represents.
+///
+/// Captured from `lstat` rather than `stat` so symlinks are recorded as
+/// links (with their `readlink` target) instead of being silently
+/// dereferenced. This is the same entry-type split archive formats like
+/// tar use (regular/symlink/hardlink/char/block/fifo), and it lets the
+/// store round-trip a tree with links instead of duplicating or
+/// dereferencing them.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum EntryType {
+    File,
+    Dir,
+    Symlink {
+        #[with(AsString)]
+        target: PathBuf,
+    },
+    HardLink {
+        #[with(AsString)]
+        to: PathBuf,
+    },
+    Fifo,
+    Socket,
+    CharDev,
+    BlockDev,
+}
+
+/// Captured POSIX ACLs for an entry.
+///
+/// Stored as their long text form (one entry per line, as rendered by
+/// `acl_to_text`) rather than the binary `acl_t` representation, so the
+/// codec doesn't need to understand libacl's in-memory layout. `default`
+/// is only ever populated for directories: it's the ACL newly created
+/// children inherit, distinct from `access`, which governs the entry itself.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct Acl {
+    pub access: Option<String>,
+    pub default: Option<String>,
+}
+
+/// Tracks `(st_dev, st_ino)` pairs seen earlier in a directory walk.
+///
+/// The first entry for a given inode is recorded as the canonical
+/// `EntryType::File`; any later entry sharing that inode (`st_nlink > 1`)
+/// becomes an `EntryType::HardLink` pointing back at the first path,
+/// instead of being captured as an independent copy of the content. The
+/// path stored here is always the partial/virtual path (pre-`BackStore`
+/// resolution), matching the path space `EntryType::HardLink { to }` and
+/// `archive`'s extraction bookkeeping use.
+#[derive(Debug, Clone, Default)]
+pub struct HardLinkTracker {
+    seen: std::collections::HashMap<(u64, u64), PathBuf>,
+}
+
+impl HardLinkTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 /// Entry in the metadata history of a file or directory.
 /// this is used to track the previous stats of a file or directory
 /// when it is modified.
 #[derive(Debug, Clone, Archive, Serialize, Deserialize)]
+#[archive(check_bytes)]
 pub struct EntryMetaData<B = AiFs>
 where
     B: BackStore + std::fmt::Debug ,
@@ -40,6 +231,7 @@ where
     pub name: OsString,
     #[with(AsString)]
     pub parent: PathBuf,
+    pub entry_type: EntryType,
     #[with(crate::codec::FileStatCodec)]
     pub stats: ::nix::sys::stat::FileStat,
     #[with(crate::codec::UserCodec)]
@@ -50,6 +242,12 @@ where
 
     pub xattrs: Option<Vec<(CString, Vec<u8>)>>,
 
+    pub acls: Option<Acl>,
+
+    /// Birth (creation) time as `(sec, nsec)`, from `statx(STATX_BTIME)`.
+    /// `None` where the kernel or filesystem doesn't report one.
+    pub btime: Option<(i64, i64)>,
+
     #[with(rkyv::with::Skip)]
     pub _fs: PhantomData<B>,
 }
@@ -59,11 +257,14 @@ impl std::fmt::Debug for ArchivedEntryMetaData {
         f.debug_struct("ArchivedEntryMetaData")
             .field("name", &self.name)
             .field("parent", &self.parent)
+            .field("entry_type", &self.entry_type)
             .field("stats", &self.stats)
             .field("owner", &self.owner)
             .field("group", &self.group)
             .field("timestamp", &self.timestamp)
             .field("xattrs", &self.xattrs)
+            .field("acls", &self.acls)
+            .field("btime", &self.btime)
             .finish()
     }
 }
@@ -72,16 +273,22 @@ impl<B> EntryMetaData<B>
 where
     B: BackStore + std::fmt::Debug + Clone,
 {
-    pub fn new<Partial>(fs: &B, parent: Partial, name: &OsStr) -> Result<Self, i32>
+    pub fn new<Partial>(
+        fs: &B,
+        parent: Partial,
+        name: &OsStr,
+        links: &mut HardLinkTracker,
+    ) -> Result<Self, i32>
     where
         Partial: AsRef<Path>,
     {
         let parent = parent.as_ref();
         let name = name.to_os_string();
-        let path = parent.join(&name);
-        let path = fs.highest_path(path)?;
+        let virtual_path = parent.join(&name);
+        let path = fs.highest_path(&virtual_path)?;
         let path = path.as_path();
-        let stats = ::nix::sys::stat::stat(path).map_err(|e| e as i32)?;
+        let stats = ::nix::sys::stat::lstat(path).map_err(|e| e as i32)?;
+        let entry_type = Self::classify(path, &virtual_path, &stats, links)?;
         let user = nix::unistd::Uid::from_raw(stats.st_uid);
         let mut owner = ::nix::unistd::User::from_uid(user)
             .map_err(|e| e as i32)?
@@ -107,10 +314,25 @@ where
         owner.passwd = CString::from_vec_with_nul(b"x\0".to_vec()).unwrap();
         group.passwd = CString::from_vec_with_nul(b"x\0".to_vec()).unwrap();
 
-        let xattrs_len = wrappers::listxattr(path, &mut [0; 0]).unwrap_or(0);
+        // Symlinks get their own xattr set, distinct from whatever they
+        // point to; reading it requires the `l*` variants so we don't
+        // silently dereference the link.
+        let is_symlink = matches!(entry_type, EntryType::Symlink { .. });
+        let list_fn: fn(&Path, &mut [u8]) -> Result<usize, libc::c_int> = if is_symlink {
+            wrappers::llistxattr
+        } else {
+            wrappers::listxattr
+        };
+        let get_fn: fn(&Path, &OsStr) -> Result<Vec<u8>, libc::c_int> = if is_symlink {
+            wrappers::lgetxattr
+        } else {
+            wrappers::getxattr
+        };
+
+        let xattrs_len = list_fn(path, &mut [0; 0]).unwrap_or(0);
         let xattrs = if xattrs_len > 0 {
             let mut xattrs = vec![0; xattrs_len];
-            wrappers::listxattr(path, &mut xattrs).unwrap();
+            list_fn(path, &mut xattrs).unwrap();
             let xattrs = xattrs
                 .split(|x| *x == 0)
                 .map(|x| CString::from_vec_with_nul(x.to_vec()).ok())
@@ -120,7 +342,7 @@ where
                 .flatten()
                 .map(|x| {
                     let _x = OsString::from_vec(x.clone().into_bytes());
-                    let value = wrappers::getxattr(path, &_x).unwrap_or(Vec::new());
+                    let value = get_fn(path, &_x).unwrap_or(Vec::new());
                     (x, value)
                 })
                 .collect::<Vec<_>>();
@@ -129,23 +351,187 @@ where
             None
         };
 
+        let access_acl = wrappers::getacl(path, wrappers::AclType::Access).unwrap_or(None);
+        let default_acl = if matches!(entry_type, EntryType::Dir) {
+            wrappers::getacl(path, wrappers::AclType::Default).unwrap_or(None)
+        } else {
+            None
+        };
+        let acls = if access_acl.is_some() || default_acl.is_some() {
+            Some(Acl {
+                access: access_acl,
+                default: default_acl,
+            })
+        } else {
+            None
+        };
+
         // time when metadata was created
         let timestamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
             .as_secs();
+
+        let btime = wrappers::statx_btime(path).unwrap_or(None);
+
         Ok(Self {
             name,
             parent: parent.into(),
+            entry_type,
             stats,
             owner,
             group,
             timestamp,
             xattrs,
+            acls,
+            btime,
             _fs: PhantomData::default(),
         })
     }
 
+    /// Last access time, in whole seconds. See [`Self::atime_nsec`] for
+    /// the sub-second remainder.
+    pub fn atime(&self) -> i64 {
+        self.stats.st_atime
+    }
+
+    /// Nanosecond remainder of [`Self::atime`].
+    pub fn atime_nsec(&self) -> i64 {
+        self.stats.st_atime_nsec
+    }
+
+    /// Last modification time, in whole seconds. See [`Self::mtime_nsec`]
+    /// for the sub-second remainder.
+    pub fn mtime(&self) -> i64 {
+        self.stats.st_mtime
+    }
+
+    /// Nanosecond remainder of [`Self::mtime`].
+    pub fn mtime_nsec(&self) -> i64 {
+        self.stats.st_mtime_nsec
+    }
+
+    /// Last status-change time, in whole seconds. See [`Self::ctime_nsec`]
+    /// for the sub-second remainder.
+    pub fn ctime(&self) -> i64 {
+        self.stats.st_ctime
+    }
+
+    /// Nanosecond remainder of [`Self::ctime`].
+    pub fn ctime_nsec(&self) -> i64 {
+        self.stats.st_ctime_nsec
+    }
+
+    /// Birth (creation) time, in whole seconds, if the kernel and
+    /// filesystem report one.
+    pub fn birth_time(&self) -> Option<i64> {
+        self.btime.map(|(sec, _)| sec)
+    }
+
+    /// Nanosecond remainder of [`Self::birth_time`].
+    pub fn birth_time_nsec(&self) -> Option<i64> {
+        self.btime.map(|(_, nsec)| nsec)
+    }
+
+    /// Classify a path's node type from its `lstat`, folding repeated
+    /// inodes seen earlier in the walk into `HardLink` entries.
+    ///
+    /// `path` is the resolved backstore path (used for the actual
+    /// `readlink` syscall), while `virtual_path` is the partial/virtual
+    /// path callers address the entry by. `HardLink { to }` must store
+    /// `virtual_path`, not `path`: it's looked up later against other
+    /// entries' partial paths (e.g. `archive`'s `parent.join(&name)` keys),
+    /// and a resolved backstore path from one machine is meaningless — or
+    /// worse, accidentally valid — on another.
+    fn classify(
+        path: &Path,
+        virtual_path: &Path,
+        stats: &::nix::sys::stat::FileStat,
+        links: &mut HardLinkTracker,
+    ) -> Result<EntryType, i32> {
+        let mode = ::nix::sys::stat::SFlag::from_bits_truncate(stats.st_mode);
+
+        if mode.contains(::nix::sys::stat::SFlag::S_IFLNK) {
+            let target = std::fs::read_link(path).map_err(|e| e.raw_os_error().unwrap_or(-1))?;
+            return Ok(EntryType::Symlink { target });
+        }
+        if mode.contains(::nix::sys::stat::SFlag::S_IFDIR) {
+            return Ok(EntryType::Dir);
+        }
+        if mode.contains(::nix::sys::stat::SFlag::S_IFIFO) {
+            return Ok(EntryType::Fifo);
+        }
+        if mode.contains(::nix::sys::stat::SFlag::S_IFSOCK) {
+            return Ok(EntryType::Socket);
+        }
+        if mode.contains(::nix::sys::stat::SFlag::S_IFCHR) {
+            return Ok(EntryType::CharDev);
+        }
+        if mode.contains(::nix::sys::stat::SFlag::S_IFBLK) {
+            return Ok(EntryType::BlockDev);
+        }
+
+        if stats.st_nlink > 1 {
+            let inode = (stats.st_dev, stats.st_ino);
+            if let Some(first) = links.seen.get(&inode) {
+                return Ok(EntryType::HardLink { to: first.clone() });
+            }
+            links.seen.insert(inode, virtual_path.to_path_buf());
+        }
+        Ok(EntryType::File)
+    }
+
+    /// Canonical byte encoding used for content hashing.
+    ///
+    /// Independent of rkyv's archived layout (no padding or
+    /// alignment-dependent bytes): fields are concatenated in a fixed
+    /// order with explicit `(len, bytes)` framing, and `xattrs` are
+    /// sorted by name first so the encoding doesn't depend on the order
+    /// `listxattr` happened to return them in.
+    fn canonical_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        write_framed(&mut buf, self.name.as_bytes());
+        write_framed(&mut buf, self.parent.as_os_str().as_bytes());
+
+        buf.extend_from_slice(&self.stats.st_mode.to_le_bytes());
+        buf.extend_from_slice(&self.stats.st_uid.to_le_bytes());
+        buf.extend_from_slice(&self.stats.st_gid.to_le_bytes());
+        buf.extend_from_slice(&self.stats.st_size.to_le_bytes());
+        buf.extend_from_slice(&self.stats.st_mtime.to_le_bytes());
+        buf.extend_from_slice(&self.stats.st_mtime_nsec.to_le_bytes());
+
+        let mut xattrs = self.xattrs.clone().unwrap_or_default();
+        xattrs.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+        for (name, value) in &xattrs {
+            write_framed(&mut buf, name.as_bytes());
+            write_framed(&mut buf, value);
+        }
+
+        buf
+    }
+
+    /// Compute a deterministic, content-addressed hash of this entry.
+    ///
+    /// Uses SHA2-256 over [`Self::canonical_bytes`]; see
+    /// [`Self::content_hash_sha3`] for the SHA3-256 variant.
+    pub fn content_hash(&self) -> EntryHash {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.canonical_bytes());
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(hasher.finalize().as_slice());
+        EntryHash::SHA2(digest)
+    }
+
+    /// Same canonical encoding as [`Self::content_hash`], but using SHA3-256.
+    pub fn content_hash_sha3(&self) -> EntryHash {
+        let mut hasher = Sha3_256::new();
+        hasher.update(&self.canonical_bytes());
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(hasher.finalize().as_slice());
+        EntryHash::SHA3(digest)
+    }
+
     pub fn to_bytes(&self) -> Result<Vec<u8>, i32> {
         let bytes: AlignedVec = CodecSerializer::<AllocSerializer<1024>>::encode(self)
             .unwrap()
@@ -155,23 +541,57 @@ where
     }
     /// write the metadata to the backstore
     /// we consume self because we don't want to write the same metadata twice
+    ///
+    /// Appends a framed `[u32 len][u8 hash-algo][32-byte chain-head
+    /// hash][len bytes of rkyv payload]` record to `.metadata.history`,
+    /// then overwrites `.metadata` with the same payload so readers that
+    /// only care about the latest version don't need to replay the
+    /// journal.
+    ///
+    /// The stored hash is not `content_hash()` on its own: it's
+    /// `H(prev_head_hash || content_hash)`, chained against whatever head
+    /// hash the journal already ends in (or the empty string, for the
+    /// first entry). That's what makes `MetaDataHistory` tamper-evident —
+    /// see [`MetaDataHistory::load`], which recomputes and checks this
+    /// chain on read. `algo` picks SHA2-256 or SHA3-256 for this entry;
+    /// mixing algorithms across entries in one journal is fine, since
+    /// each frame carries its own algorithm tag.
     // TODO: create a timeout for the lock (using parking_lot::Mutex)
-    pub fn write(self, fs: &B) -> Result<(), i32> {
+    pub fn write(self, fs: &B, algo: HashAlgo) -> Result<(), i32> {
         let partial = self.parent.as_path().join(&self.name);
-        let path = fs.metadir_path(partial)?;
-        std::fs::create_dir_all(&path).map_err(|e| e.raw_os_error().unwrap_or(-1))?;
-        let path = path.join(".metadata");
-        let _history_path = path.join(".metadata.history");
+        let dir = fs.metadir_path(partial)?;
+        std::fs::create_dir_all(&dir).map_err(|e| e.raw_os_error().unwrap_or(-1))?;
+        let metadata_path = dir.join(".metadata");
+        let history_path = dir.join(".metadata.history");
+
         let opts = FileOptions::new()
             .write(true)
             .create(true)
-            .append(false)
+            .append(true)
             .read(true);
+        let mut lock = FileLock::lock(&history_path, true, opts)
+            .map_err(|e| e.raw_os_error().unwrap_or(EBUSY))?;
+
+        let mut existing = Vec::new();
+        lock.file
+            .read_to_end(&mut existing)
+            .map_err(|e| e.raw_os_error().unwrap_or(-1))?;
+        let prev_head = parse_frames(&existing).last().map(|(hash, _)| hash);
+
+        let content_hash = match algo {
+            HashAlgo::Sha2 => self.content_hash(),
+            HashAlgo::Sha3 => self.content_hash_sha3(),
+        };
+        let chain_hash = EntryHash::chain(prev_head.as_ref(), &content_hash);
+
+        let payload = self.to_bytes()?;
+        let record = frame_record(&chain_hash, &payload);
 
-        let mut _lock =
-            FileLock::lock(&path, true, opts).map_err(|e| e.raw_os_error().unwrap_or(EBUSY))?;
+        lock.file
+            .write_all(&record)
+            .map_err(|e| e.raw_os_error().unwrap_or(-1))?;
 
-        // _lock.file.write_all(buf.as_ref()).map_err(|e| e.raw_os_error().unwrap_or(-1))?;
+        std::fs::write(&metadata_path, &payload).map_err(|e| e.raw_os_error().unwrap_or(-1))?;
 
         Ok(())
     }
@@ -187,6 +607,60 @@ where
     pub emd: Vec<EntryMetaData<B>>,
 }
 
+impl<B> MetaDataHistory<B>
+where
+    B: BackStore + std::fmt::Debug + Clone,
+{
+    /// Load the full `.metadata.history` journal written by
+    /// [`EntryMetaData::write`].
+    ///
+    /// Replays the framed records in append order, deserializing each
+    /// rkyv payload back into an `EntryMetaData`, and re-verifies the
+    /// Merkle chain as it goes: each stored hash must equal
+    /// `H(prev_head_hash || content_hash)` of the entry deserialized from
+    /// that frame. A mismatch means the journal was truncated, reordered,
+    /// or tampered with, so loading fails outright rather than returning
+    /// a partial or unverified history. `hash` is the chain head — the
+    /// hash of the most recent (last) entry in the journal.
+    pub fn load<Partial>(fs: &B, partial: Partial) -> Result<Self, i32>
+    where
+        Partial: AsRef<Path> + std::fmt::Debug,
+    {
+        let name = partial.as_ref().to_string_lossy().into_owned();
+        let dir = fs.metadir_path(&partial)?;
+        let history_path = dir.join(".metadata.history");
+        let bytes =
+            std::fs::read(&history_path).map_err(|e| e.raw_os_error().unwrap_or(libc::ENOENT))?;
+
+        let mut emd = Vec::new();
+        let mut head = None;
+        for (stored_hash, payload) in parse_frames(&bytes) {
+            let archived =
+                rkyv::check_archived_root::<EntryMetaData<B>>(payload).map_err(|_| libc::EIO)?;
+            let entry: EntryMetaData<B> =
+                ArchivedEntryMetaData::deserialize(archived, &mut rkyv::Infallible).unwrap();
+
+            let content_hash = match stored_hash {
+                EntryHash::SHA2(_) => entry.content_hash(),
+                EntryHash::SHA3(_) => entry.content_hash_sha3(),
+            };
+            let expected = EntryHash::chain(head.as_ref(), &content_hash);
+            if expected.as_bytes() != stored_hash.as_bytes() {
+                return Err(libc::EIO);
+            }
+
+            head = Some(stored_hash);
+            emd.push(entry);
+        }
+
+        Ok(Self {
+            name,
+            hash: head.ok_or(libc::ENOENT)?,
+            emd,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rkyv::archived_root;
@@ -196,7 +670,8 @@ mod tests {
     #[test]
     fn test_metadata() {
         let fs = AiFs::new("/tmp/aifs", "/tmp/lower", None);
-        let emd = EntryMetaData::new(&fs, "/", OsStr::new("test.txt")).unwrap();
+        let mut links = HardLinkTracker::new();
+        let emd = EntryMetaData::new(&fs, "/", OsStr::new("test.txt"), &mut links).unwrap();
         eprintln!("{:?}", emd);
         let bytes = emd.to_bytes().unwrap();
         let emd2 = unsafe { archived_root::<EntryMetaData>(bytes.as_slice()) };
@@ -207,6 +682,69 @@ mod tests {
         eprintln!("{:?}", emd2);
     }
 
+    #[test]
+    fn test_history_round_trip_and_tamper_detection() {
+        std::fs::write("/tmp/foo/highest/test.txt", b"contents").unwrap();
+
+        let fs = AiFs::new("/tmp/aifs", "/tmp/lower", None);
+        let mut links = HardLinkTracker::new();
+        let emd = EntryMetaData::new(&fs, "", OsStr::new("test.txt"), &mut links).unwrap();
+
+        let dir = fs.metadir_path("test.txt").unwrap();
+        let history_path = dir.join(".metadata.history");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        emd.clone().write(&fs, HashAlgo::Sha2).unwrap();
+        emd.write(&fs, HashAlgo::Sha3).unwrap();
+
+        let history = MetaDataHistory::load(&fs, "test.txt").unwrap();
+        assert_eq!(history.emd.len(), 2);
+
+        // Invert the first byte of the first frame's stored digest and confirm load()
+        // now detects the tamper instead of silently trusting it.
+        let mut bytes = std::fs::read(&history_path).unwrap();
+        bytes[5] ^= 0xff;
+        std::fs::write(&history_path, &bytes).unwrap();
+
+        assert_eq!(
+            MetaDataHistory::load(&fs, "test.txt").unwrap_err(),
+            libc::EIO
+        );
+    }
+
+    #[test]
+    fn test_nanosecond_timestamp_accessors() {
+        let path = Path::new("/tmp/foo/highest/meta_test_nsec.txt");
+        let _ = std::fs::remove_file(path);
+        std::fs::write(path, b"contents").unwrap();
+
+        let fs = AiFs::new("/tmp/aifs", "/tmp/lower", None);
+        let mut links = HardLinkTracker::new();
+        let emd =
+            EntryMetaData::new(&fs, "", OsStr::new("meta_test_nsec.txt"), &mut links).unwrap();
+
+        let raw = ::nix::sys::stat::lstat(path).unwrap();
+        assert_eq!(emd.atime(), raw.st_atime);
+        assert_eq!(emd.atime_nsec(), raw.st_atime_nsec);
+        assert_eq!(emd.mtime(), raw.st_mtime);
+        assert_eq!(emd.mtime_nsec(), raw.st_mtime_nsec);
+        assert_eq!(emd.ctime(), raw.st_ctime);
+        assert_eq!(emd.ctime_nsec(), raw.st_ctime_nsec);
+
+        // btime is best-effort: only assert it's internally consistent
+        // with the accessors when the filesystem actually reports one.
+        match emd.btime {
+            Some((sec, nsec)) => {
+                assert_eq!(emd.birth_time(), Some(sec));
+                assert_eq!(emd.birth_time_nsec(), Some(nsec));
+            }
+            None => {
+                assert_eq!(emd.birth_time(), None);
+                assert_eq!(emd.birth_time_nsec(), None);
+            }
+        }
+    }
+
     #[test]
     fn test_nix_user() {
         use nix::unistd::{Uid, User};
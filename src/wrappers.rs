@@ -9,6 +9,7 @@ use std::io;
 use std::mem;
 use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
+use std::ptr;
 
 #[allow(unused_imports)]
 pub(crate) mod consts {
@@ -78,30 +79,91 @@ macro_rules! syscall_errno {
     }};
 }
 
+/// Two-pass, binary-safe `getxattr`/`lgetxattr` call: first ask the kernel
+/// how large the value is, then allocate exactly that many bytes and read
+/// the whole thing, retrying on `ERANGE` if the value grew between the
+/// two calls. Unlike a fixed-buffer-plus-`CStr::from_ptr` read, this
+/// doesn't truncate binary values (ACLs, capabilities, IMA/EVM
+/// signatures) at their first NUL byte.
+fn getxattr_two_pass(
+    path_c: &CString,
+    name_c: &CString,
+    syscall: unsafe extern "C" fn(
+        *const libc::c_char,
+        *const libc::c_char,
+        *mut libc::c_void,
+        libc::size_t,
+    ) -> libc::ssize_t,
+) -> Result<Vec<u8>, libc::c_int> {
+    loop {
+        let size = unsafe { syscall(path_c.as_ptr(), name_c.as_ptr(), ptr::null_mut(), 0) };
+        if size < 0 {
+            return Err(io::Error::last_os_error().raw_os_error().unwrap_or(-1));
+        }
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut buf = vec![0u8; size as usize];
+        let read = unsafe {
+            syscall(
+                path_c.as_ptr(),
+                name_c.as_ptr(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+            )
+        };
+        if read < 0 {
+            let errno = io::Error::last_os_error().raw_os_error().unwrap_or(-1);
+            if errno == libc::ERANGE {
+                // Value grew between the size probe and the read; retry.
+                continue;
+            }
+            return Err(errno);
+        }
+
+        buf.truncate(read as usize);
+        return Ok(buf);
+    }
+}
+
 #[inline]
 pub fn getxattr(path: &Path, name: &OsStr) -> Result<Vec<u8>, libc::c_int> {
     let path_c = into_cstring!(path, "getxattr");
     let name_c = into_cstring!(name, "getxattr");
+    getxattr_two_pass(&path_c, &name_c, libc::getxattr)
+}
 
-    let mut buf: [libc::c_char; libc::PATH_MAX as usize] = unsafe { mem::zeroed() };
-    syscall_errno! {
-        libc::getxattr(
+/// Symlink-aware `getxattr`: reads the xattr of the link itself rather
+/// than of whatever it points to.
+#[inline]
+pub fn lgetxattr(path: &Path, name: &OsStr) -> Result<Vec<u8>, libc::c_int> {
+    let path_c = into_cstring!(path, "lgetxattr");
+    let name_c = into_cstring!(name, "lgetxattr");
+    getxattr_two_pass(&path_c, &name_c, libc::lgetxattr)
+}
+
+#[inline]
+pub fn listxattr(path: &Path, buf: &mut [u8]) -> Result<usize, libc::c_int> {
+    let path_c = into_cstring!(path, "listxattr");
+
+    Ok(syscall_errno!(
+        return libc::listxattr(
             path_c.as_ptr(),
-            name_c.as_ptr(),
-            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.as_mut_ptr() as *mut libc::c_char,
             buf.len(),
-        ),
-        return Ok(Vec::from(unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }.to_bytes()))
-    }
+        )
+    ) as usize)
 }
 
-
+/// Symlink-aware `listxattr`: lists the xattrs of the link itself rather
+/// than of whatever it points to.
 #[inline]
-pub fn listxattr(path: &Path, buf: &mut [u8]) -> Result<usize, libc::c_int> {
+pub fn llistxattr(path: &Path, buf: &mut [u8]) -> Result<usize, libc::c_int> {
     let path_c = into_cstring!(path, "llistxattr");
 
     Ok(syscall_errno!(
-        return libc::listxattr(
+        return libc::llistxattr(
             path_c.as_ptr(),
             buf.as_mut_ptr() as *mut libc::c_char,
             buf.len(),
@@ -109,3 +171,218 @@ pub fn listxattr(path: &Path, buf: &mut [u8]) -> Result<usize, libc::c_int> {
     ) as usize)
 }
 
+#[inline]
+pub fn setxattr(path: &Path, name: &OsStr, value: &[u8]) -> Result<(), libc::c_int> {
+    let path_c = into_cstring!(path, "setxattr");
+    let name_c = into_cstring!(name, "setxattr");
+
+    syscall_errno!(libc::setxattr(
+        path_c.as_ptr(),
+        name_c.as_ptr(),
+        value.as_ptr() as *const libc::c_void,
+        value.len(),
+        0,
+    ))
+}
+
+/// Raw FFI bindings for the parts of libacl we need.
+///
+/// `libc` doesn't carry POSIX.1e ACL declarations (they live in
+/// `<sys/acl.h>`/`<acl/libacl.h>`, shipped by libacl, not glibc), so we
+/// declare the handful of entry points ourselves and link against it.
+#[allow(non_camel_case_types)]
+mod acl {
+    pub type acl_t = *mut libc::c_void;
+
+    pub const ACL_TYPE_ACCESS: libc::c_int = 0x8000;
+    pub const ACL_TYPE_DEFAULT: libc::c_int = 0x4000;
+
+    #[link(name = "acl")]
+    extern "C" {
+        pub fn acl_get_file(path: *const libc::c_char, acl_type: libc::c_int) -> acl_t;
+        pub fn acl_to_text(acl: acl_t, len: *mut libc::ssize_t) -> *mut libc::c_char;
+        pub fn acl_from_text(buf: *const libc::c_char) -> acl_t;
+        pub fn acl_set_file(path: *const libc::c_char, acl_type: libc::c_int, acl: acl_t) -> libc::c_int;
+        pub fn acl_free(obj: *mut libc::c_void) -> libc::c_int;
+    }
+}
+
+/// Which of a file's two possible ACLs to fetch.
+///
+/// `Default` only applies to directories: it's the ACL newly created
+/// children inherit, as opposed to `Access`, which governs the entry
+/// itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclType {
+    Access,
+    Default,
+}
+
+impl AclType {
+    fn as_raw(self) -> libc::c_int {
+        match self {
+            AclType::Access => acl::ACL_TYPE_ACCESS,
+            AclType::Default => acl::ACL_TYPE_DEFAULT,
+        }
+    }
+}
+
+/// Read a POSIX ACL from `path`, returning its long text form (one entry
+/// per line, e.g. `user::rwx\ngroup::r-x\nother::r--\n`) as `acl_to_text`
+/// renders it.
+///
+/// Returns `Ok(None)` when the filesystem or inode has no ACL of this
+/// type set (`ENODATA`) or doesn't support ACLs at all (`ENOTSUP`/`ENOSYS`),
+/// since both are expected, not exceptional.
+pub fn getacl(path: &Path, acl_type: AclType) -> Result<Option<String>, libc::c_int> {
+    let path_c = into_cstring!(path, "acl_get_file");
+
+    let handle = unsafe { acl::acl_get_file(path_c.as_ptr(), acl_type.as_raw()) };
+    if handle.is_null() {
+        return match io::Error::last_os_error().raw_os_error() {
+            Some(libc::ENODATA) | Some(libc::ENOTSUP) | Some(libc::ENOSYS) => Ok(None),
+            Some(errno) => Err(errno),
+            None => Err(-1),
+        };
+    }
+
+    let mut len: libc::ssize_t = 0;
+    let text = unsafe { acl::acl_to_text(handle, &mut len) };
+    let rendered = if text.is_null() {
+        None
+    } else {
+        let bytes = unsafe { std::ffi::CStr::from_ptr(text) }.to_bytes().to_vec();
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    };
+
+    unsafe {
+        if !text.is_null() {
+            acl::acl_free(text as *mut libc::c_void);
+        }
+        acl::acl_free(handle as *mut libc::c_void);
+    }
+
+    Ok(rendered)
+}
+
+/// Apply a POSIX ACL to `path` from its text form (such as the long
+/// form [`getacl`] returns), via `acl_from_text`/`acl_set_file`.
+pub fn setacl(path: &Path, acl_type: AclType, text: &str) -> Result<(), libc::c_int> {
+    let path_c = into_cstring!(path, "acl_set_file");
+    let text_c = CString::new(text).map_err(|_| libc::EINVAL)?;
+
+    let handle = unsafe { acl::acl_from_text(text_c.as_ptr()) };
+    if handle.is_null() {
+        return Err(io::Error::last_os_error().raw_os_error().unwrap_or(-1));
+    }
+
+    let ret = unsafe { acl::acl_set_file(path_c.as_ptr(), acl_type.as_raw(), handle) };
+    let err = if ret < 0 {
+        Some(io::Error::last_os_error().raw_os_error().unwrap_or(-1))
+    } else {
+        None
+    };
+
+    unsafe {
+        acl::acl_free(handle as *mut libc::c_void);
+    }
+
+    match err {
+        Some(errno) => Err(errno),
+        None => Ok(()),
+    }
+}
+
+/// Birth (creation) time via `statx(STATX_BTIME)`, as `(sec, nsec)`.
+///
+/// Returns `Ok(None)` rather than an error when the kernel or filesystem
+/// doesn't report a birth time (`stx_mask` comes back without
+/// `STATX_BTIME` set) — that's the normal case on many filesystems, not
+/// a failure.
+#[cfg(target_os = "linux")]
+pub fn statx_btime(path: &Path) -> Result<Option<(i64, i64)>, libc::c_int> {
+    let path_c = into_cstring!(path, "statx");
+
+    let mut stx: libc::statx = unsafe { mem::zeroed() };
+    let ret = unsafe {
+        libc::statx(
+            libc::AT_FDCWD,
+            path_c.as_ptr(),
+            libc::AT_SYMLINK_NOFOLLOW,
+            libc::STATX_BTIME,
+            &mut stx,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error().raw_os_error().unwrap_or(-1));
+    }
+    if stx.stx_mask & libc::STATX_BTIME == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some((stx.stx_btime.tv_sec, stx.stx_btime.tv_nsec as i64)))
+}
+
+/// No `statx`/birth-time support outside Linux; callers treat this the
+/// same as "filesystem doesn't report one".
+#[cfg(not(target_os = "linux"))]
+pub fn statx_btime(_path: &Path) -> Result<Option<(i64, i64)>, libc::c_int> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_getxattr_two_pass_binary_safe() {
+        let path = Path::new("/tmp/foo/highest/wrappers_test_xattr.txt");
+        let _ = std::fs::remove_file(path);
+        std::fs::write(path, b"contents").unwrap();
+
+        // A value with an embedded NUL, larger than a single small
+        // fixed-size buffer, to exercise the probe-then-read two-pass
+        // path rather than a `CStr::from_ptr` read that would truncate it.
+        let mut value = vec![0xABu8; 256];
+        value[100] = 0;
+        setxattr(path, OsStr::new("user.rkyv_codec_test"), &value).unwrap();
+
+        let read_back = getxattr(path, OsStr::new("user.rkyv_codec_test")).unwrap();
+        assert_eq!(read_back, value);
+    }
+
+    #[test]
+    fn test_lgetxattr_does_not_follow_symlink() {
+        let target = Path::new("/tmp/foo/highest/wrappers_test_target.txt");
+        let link = Path::new("/tmp/foo/highest/wrappers_test_link.txt");
+        let _ = std::fs::remove_file(target);
+        let _ = std::fs::remove_file(link);
+        std::fs::write(target, b"target contents").unwrap();
+        std::os::unix::fs::symlink(target, link).unwrap();
+
+        setxattr(target, OsStr::new("user.rkyv_codec_test2"), b"on target").unwrap();
+
+        // Plain getxattr follows the symlink and sees the target's attribute...
+        let via_target = getxattr(link, OsStr::new("user.rkyv_codec_test2")).unwrap();
+        assert_eq!(via_target, b"on target");
+
+        // ...but lgetxattr reads the link itself, which has no such attribute.
+        assert!(lgetxattr(link, OsStr::new("user.rkyv_codec_test2")).is_err());
+    }
+
+    #[test]
+    fn test_statx_btime_none_fallback() {
+        let path = Path::new("/tmp/foo/highest/wrappers_test_btime.txt");
+        let _ = std::fs::remove_file(path);
+        std::fs::write(path, b"contents").unwrap();
+
+        // Whether or not the underlying filesystem actually reports a
+        // birth time, the call itself must succeed: a filesystem that
+        // doesn't report one is the `Ok(None)` fallback path, not an error.
+        let btime = statx_btime(path).unwrap();
+        if let Some((sec, nsec)) = btime {
+            assert!(sec > 0);
+            assert!((0..1_000_000_000).contains(&nsec));
+        }
+    }
+}